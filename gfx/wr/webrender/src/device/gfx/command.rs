@@ -2,9 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use std::collections::HashMap;
+use std::iter;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
 use hal::command::{CommandBufferFlags, CommandBuffer};
 use hal::device::Device as BackendDevice;
-use hal::pool::CommandPool as HalCommandPool;
+use hal::pool::{CommandPool as HalCommandPool, CommandPoolCreateFlags};
+use hal::queue::QueueFamilyId;
+
+/// Monotonically increasing identifier for a queue submission, used to tell
+/// when the GPU is done with a command buffer.
+pub(super) type SubmissionIndex = usize;
+
+/// Number of command buffers allocated in one batch when the pool runs dry.
+pub(super) const DEFAULT_GROW_AMOUNT: usize = 20;
 
 pub(super) enum CBStrategy {
     UseOne,
@@ -14,6 +27,14 @@ pub(super) enum CBStrategy {
 pub struct CommandPool<B: hal::Backend> {
     command_pool: B::CommandPool,
     command_buffers: Vec<B::CommandBuffer>,
+    // How many buffers to allocate in one go when `available` is exhausted.
+    grow_amount: usize,
+    // Buffers submitted to the GPU, tagged with the submission they belong to.
+    // They can be reused once that submission's fence has passed.
+    pending: Vec<(B::CommandBuffer, SubmissionIndex)>,
+    // Buffers whose submission has completed and which are ready to hand out
+    // again without touching the backend.
+    available: Vec<B::CommandBuffer>,
     strategy: CBStrategy,
     next_id: usize,
     begin: bool,
@@ -24,19 +45,34 @@ impl<B: hal::Backend> CommandPool<B> {
         CommandPool {
             command_pool,
             command_buffers: vec![],
+            // How many buffers a single `grow` call allocates in one batch.
+            grow_amount: DEFAULT_GROW_AMOUNT,
+            pending: vec![],
+            available: vec![],
             strategy: CBStrategy::AllocateNew,
             next_id: 0,
             begin: true,
         }
     }
 
+    // Allocate a fresh batch of buffers into `available` in a single backend
+    // call, amortizing the per-allocation overhead under heavy submission.
+    fn grow(&mut self) {
+        let buffers = unsafe {
+            self.command_pool
+                .allocate(self.grow_amount, hal::command::Level::Primary)
+        };
+        self.available.extend(buffers);
+    }
+
     pub(super) fn buffer_mut(&mut self, inside_render_pass: bool) -> &mut B::CommandBuffer {
         match self.strategy {
             CBStrategy::UseOne => {
                 if self.command_buffers.len() < 1 {
-                    let mut command_buffer = unsafe {
-                        self.command_pool.allocate_one(hal::command::Level::Primary)
-                    };
+                    if self.available.is_empty() {
+                        self.grow();
+                    }
+                    let command_buffer = self.available.pop().unwrap();
                     self.command_buffers.push(command_buffer);
                 }
                 let command_buffer = self.command_buffers.get_mut(0).unwrap();
@@ -59,9 +95,10 @@ impl<B: hal::Backend> CommandPool<B> {
                     self.next_id
                 };
                 if self.command_buffers.len() <= next_id {
-                    let command_buffer = unsafe {
-                        self.command_pool.allocate_one(hal::command::Level::Primary)
-                    };
+                    if self.available.is_empty() {
+                        self.grow();
+                    }
+                    let command_buffer = self.available.pop().unwrap();
                     self.command_buffers.push(command_buffer);
                 }
                 let command_buffer = self.command_buffers.get_mut(next_id).unwrap();
@@ -104,6 +141,46 @@ impl<B: hal::Backend> CommandPool<B> {
         self.command_buffers.insert(0, cmd_buffer);
     }
 
+    /// Move the buffers recorded this frame into the pending set tagged with the
+    /// submission they were handed to, so they can be recycled once the GPU
+    /// signals completion of `submission_index`.
+    pub(super) fn submit(&mut self, submission_index: SubmissionIndex) {
+        for command_buffer in self.command_buffers.drain(..) {
+            self.pending.push((command_buffer, submission_index));
+        }
+        self.next_id = 0;
+        self.begin = true;
+    }
+
+    /// Recycle every pending buffer whose submission index has completed,
+    /// resetting each one individually and returning it to the available pool.
+    pub(super) unsafe fn maintain(&mut self, last_done_index: SubmissionIndex) {
+        for i in (0 .. self.pending.len()).rev() {
+            if self.pending[i].1 <= last_done_index {
+                let (mut command_buffer, _) = self.pending.swap_remove(i);
+                command_buffer.reset(false);
+                self.available.push(command_buffer);
+            }
+        }
+    }
+
+    /// Hand a single command buffer back to the backend, releasing its memory
+    /// immediately instead of retaining it for the lifetime of the pool.
+    pub unsafe fn free_buffer(&mut self, _device: &B::Device, buffer: B::CommandBuffer) {
+        self.command_pool.free(iter::once(buffer));
+    }
+
+    /// Free every pending buffer whose submission has completed, releasing their
+    /// memory to the backend rather than recycling them into `available`.
+    pub unsafe fn free_completed(&mut self, _device: &B::Device, last_done_index: SubmissionIndex) {
+        for i in (0 .. self.pending.len()).rev() {
+            if self.pending[i].1 <= last_done_index {
+                let (buffer, _) = self.pending.swap_remove(i);
+                self.command_pool.free(iter::once(buffer));
+            }
+        }
+    }
+
     pub(super) unsafe fn reset(&mut self) {
         self.command_pool.reset(false);
         self.next_id = 0;
@@ -114,3 +191,71 @@ impl<B: hal::Backend> CommandPool<B> {
         device.destroy_command_pool(self.command_pool);
     }
 }
+
+/// Owns one `CommandPool` per recording thread so that worker threads can
+/// encode command buffers concurrently without sharing a single
+/// `B::CommandPool`, which is not thread-safe.
+///
+/// Pools are created lazily the first time each thread records, keyed on its
+/// `ThreadId` behind a mutex, mirroring the allocator wgpu-core uses.
+pub struct CommandAllocator<B: hal::Backend> {
+    family: QueueFamilyId,
+    flags: CommandPoolCreateFlags,
+    // Each per-thread pool is behind its own `Mutex` so that the map lock is
+    // only held long enough to look the pool up, not for the duration of
+    // recording. Worker threads touch disjoint pools and never contend.
+    pools: Mutex<HashMap<ThreadId, Arc<Mutex<CommandPool<B>>>>>,
+}
+
+impl<B: hal::Backend> CommandAllocator<B> {
+    pub(super) fn new(family: QueueFamilyId, flags: CommandPoolCreateFlags) -> Self {
+        CommandAllocator {
+            family,
+            flags,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `f` against the calling thread's pool, creating the pool on first
+    /// use from that thread. This is how a thread reaches its own `buffer_mut`
+    /// without handing a reference across the pool mutex.
+    ///
+    /// The map mutex is released before `f` runs, so recording on one thread
+    /// never blocks another thread's lookup.
+    pub(super) fn with_thread_pool<T, F>(&self, device: &B::Device, f: F) -> T
+    where
+        F: FnOnce(&mut CommandPool<B>) -> T,
+    {
+        let pool = {
+            let mut pools = self.pools.lock().unwrap();
+            Arc::clone(pools.entry(thread::current().id()).or_insert_with(|| {
+                let raw = unsafe {
+                    device
+                        .create_command_pool(self.family, self.flags)
+                        .expect("Failed to create command pool")
+                };
+                Arc::new(Mutex::new(CommandPool::new(raw)))
+            }))
+        };
+        let mut pool = pool.lock().unwrap();
+        f(&mut pool)
+    }
+
+    /// Recycle finished buffers in every per-thread pool.
+    pub(super) unsafe fn maintain(&self, last_done_index: SubmissionIndex) {
+        for pool in self.pools.lock().unwrap().values() {
+            pool.lock().unwrap().maintain(last_done_index);
+        }
+    }
+
+    /// Destroy every per-thread pool.
+    pub(super) unsafe fn destroy(self, device: &B::Device) {
+        for (_, pool) in self.pools.into_inner().unwrap() {
+            let pool = Arc::try_unwrap(pool)
+                .unwrap_or_else(|_| panic!("command pool still in use at destroy"))
+                .into_inner()
+                .unwrap();
+            pool.destroy(device);
+        }
+    }
+}
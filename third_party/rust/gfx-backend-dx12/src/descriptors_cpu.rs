@@ -99,6 +99,21 @@ impl Heap {
         self.availability == 0
     }
 
+    // Returns true if `handle` was allocated from this heap.
+    pub fn has_handle(&self, handle: CpuDescriptor) -> bool {
+        handle.ptr >= self.start.ptr
+            && handle.ptr < self.start.ptr + self.handle_size * HEAP_SIZE_FIXED
+    }
+
+    pub fn free_handle(&mut self, handle: CpuDescriptor) {
+        let slot = (handle.ptr - self.start.ptr) / self.handle_size;
+        assert!(slot < HEAP_SIZE_FIXED);
+        // The slot must currently be occupied.
+        assert_eq!(self.availability & (1 << slot), 0);
+        // Mark the slot as free again.
+        self.availability |= 1 << slot;
+    }
+
     pub unsafe fn destroy(&self) {
         self.raw.destroy();
     }
@@ -143,7 +158,83 @@ impl DescriptorCpuPool {
         handle
     }
 
-    // TODO: free handles
+    pub fn free_handle(&mut self, handle: CpuDescriptor) {
+        // Find the heap that owns the handle's range.
+        let heap_id = self
+            .heaps
+            .iter()
+            .position(|heap| heap.has_handle(handle))
+            .expect("CpuDescriptor does not belong to this pool");
+
+        let heap = &mut self.heaps[heap_id];
+        let was_full = heap.is_full();
+        heap.free_handle(handle);
+        // A freshly non-full heap becomes a candidate for future allocations.
+        if was_full {
+            self.free_list.insert(heap_id);
+        }
+    }
+
+    pub unsafe fn destroy(&self) {
+        for heap in &self.heaps {
+            heap.destroy();
+        }
+    }
+}
+
+// A pool of linear (bump) CPU descriptor heaps that rolls over to a fresh
+// backing heap when the current one fills, and can be cheaply reset for reuse
+// each frame. Suited to transient descriptors that are rebuilt every frame,
+// where the free-list `Heap` path of `DescriptorCpuPool` is needlessly
+// expensive.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct DescriptorCpuLinearPool {
+    device: native::Device,
+
+    #[derivative(Debug = "ignore")]
+    ty: HeapType,
+    heaps: Vec<HeapLinear>,
+    // Number of handles per backing heap.
+    size: usize,
+    // Index of the heap we are currently allocating from.
+    cursor: usize,
+}
+
+impl DescriptorCpuLinearPool {
+    pub fn new(device: native::Device, ty: HeapType, size: usize) -> Self {
+        DescriptorCpuLinearPool {
+            device,
+            ty,
+            heaps: Vec::new(),
+            size,
+            cursor: 0,
+        }
+    }
+
+    pub fn alloc_handle(&mut self) -> CpuDescriptor {
+        loop {
+            // Allocate another backing heap once we run off the end.
+            if self.cursor == self.heaps.len() {
+                let heap = HeapLinear::new(self.device, self.ty, self.size);
+                self.heaps.push(heap);
+            }
+            // Skip over heaps that have filled up.
+            if self.heaps[self.cursor].is_full() {
+                self.cursor += 1;
+                continue;
+            }
+            return self.heaps[self.cursor].alloc_handle();
+        }
+    }
+
+    // Rewind all backing heaps for cheap per-frame bump reuse.
+    pub fn reset(&mut self) {
+        for heap in &mut self.heaps {
+            heap.clear();
+        }
+        self.cursor = 0;
+    }
 
     pub unsafe fn destroy(&self) {
         for heap in &self.heaps {
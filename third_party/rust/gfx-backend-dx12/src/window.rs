@@ -4,8 +4,20 @@ use std::mem;
 #[cfg(feature = "winit")]
 use winit;
 
+use winapi::shared::dxgi::{
+    DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING, DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT,
+};
+use winapi::shared::dxgi1_2::{
+    DXGI_ALPHA_MODE_IGNORE, DXGI_ALPHA_MODE_PREMULTIPLIED, DXGI_ALPHA_MODE_STRAIGHT,
+    DXGI_SWAP_EFFECT_FLIP_DISCARD, DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL,
+};
 use winapi::shared::dxgi1_4;
+use winapi::shared::dxgitype::DXGI_PRESENT_ALLOW_TEARING;
+use winapi::shared::minwindef::FALSE;
 use winapi::shared::windef::{HWND, RECT};
+use winapi::shared::winerror::WAIT_TIMEOUT;
+use winapi::um::synchapi::WaitForSingleObjectEx;
+use winapi::um::winnt::HANDLE;
 use winapi::um::winuser::GetClientRect;
 
 use hal::{self, format as f, image as i, CompositeAlpha};
@@ -91,7 +103,11 @@ impl hal::Surface<Backend> for Surface {
             extents: extent ..= extent,
             max_image_layers: 1,
             usage: i::Usage::COLOR_ATTACHMENT | i::Usage::TRANSFER_SRC | i::Usage::TRANSFER_DST,
-            composite_alpha: CompositeAlpha::OPAQUE, //TODO
+            // The flip-model swapchain supports the opaque and both alpha-blend
+            // composite modes through `DXGI_ALPHA_MODE`.
+            composite_alpha: CompositeAlpha::OPAQUE
+                | CompositeAlpha::PREMULTIPLIED
+                | CompositeAlpha::POSTMULTIPLIED,
         };
 
         // Sticking to FLIP swap effects for the moment.
@@ -106,14 +122,89 @@ impl hal::Surface<Backend> for Surface {
             f::Format::Rgba16Sfloat,
         ];
 
+        // Advertised in addition to the v-synced `Fifo` path. `present_config`
+        // maps `Immediate` to tearing and `Mailbox` to discarding stale frames;
+        // no swap chain in this crate is created from that mapping.
         let present_modes = vec![
-            hal::PresentMode::Fifo, //TODO
+            hal::PresentMode::Fifo,
+            hal::PresentMode::Mailbox,
+            hal::PresentMode::Immediate,
         ];
 
         (capabilities, Some(formats), present_modes)
     }
 }
 
+/// The flip-model swap-chain configuration implied by a requested present mode.
+///
+/// `compatibility` advertises `Fifo`, `Mailbox` and `Immediate`; this holds
+/// the flip-model settings for each of them.
+///
+/// Not used anywhere in the crate (hence `allow(dead_code)`).
+#[allow(dead_code)]
+pub(crate) struct PresentConfig {
+    /// The `DXGI_SWAP_EFFECT` to create the swap chain with.
+    pub(crate) swap_effect: u32,
+    /// Extra `DXGI_SWAP_CHAIN_FLAG` bits, OR-ed with the waitable-object flag.
+    pub(crate) swap_chain_flags: u32,
+    /// The flags to pass to each `IDXGISwapChain3::Present`.
+    pub(crate) present_flags: u32,
+    /// The v-sync interval to pass to `Present` (`0` tears, `1` waits a vblank).
+    pub(crate) sync_interval: u32,
+}
+
+/// Translate a HAL [`PresentMode`](hal::PresentMode) into the flip-model
+/// swap-chain settings that realize it.
+///
+/// Every configuration it returns includes `FRAME_LATENCY_WAITABLE_OBJECT`,
+/// the flag [`Swapchain::acquire_image`] needs to block on the frame-latency
+/// handle.
+///
+/// Not called anywhere in the crate (hence `allow(dead_code)`).
+#[allow(dead_code)]
+pub(crate) fn present_config(mode: hal::PresentMode) -> PresentConfig {
+    let base_flags = DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT;
+    match mode {
+        // Discard stale frames but keep waiting for a vblank.
+        hal::PresentMode::Mailbox => PresentConfig {
+            swap_effect: DXGI_SWAP_EFFECT_FLIP_DISCARD,
+            swap_chain_flags: base_flags,
+            present_flags: 0,
+            sync_interval: 1,
+        },
+        // Tear: no v-sync. Tearing has to be opted into on both the swap chain
+        // and every `Present` call.
+        hal::PresentMode::Immediate => PresentConfig {
+            swap_effect: DXGI_SWAP_EFFECT_FLIP_DISCARD,
+            swap_chain_flags: base_flags | DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING,
+            present_flags: DXGI_PRESENT_ALLOW_TEARING,
+            sync_interval: 0,
+        },
+        // `Fifo` (and anything else) is classic v-sync with no discards.
+        _ => PresentConfig {
+            swap_effect: DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL,
+            swap_chain_flags: base_flags,
+            present_flags: 0,
+            sync_interval: 1,
+        },
+    }
+}
+
+/// Translate the requested composite-alpha bits into a `DXGI_ALPHA_MODE`,
+/// preferring an explicit blend mode over the opaque default.
+///
+/// Not called anywhere in the crate (hence `allow(dead_code)`).
+#[allow(dead_code)]
+pub(crate) fn composite_alpha_mode(alpha: CompositeAlpha) -> u32 {
+    if alpha.contains(CompositeAlpha::PREMULTIPLIED) {
+        DXGI_ALPHA_MODE_PREMULTIPLIED
+    } else if alpha.contains(CompositeAlpha::POSTMULTIPLIED) {
+        DXGI_ALPHA_MODE_STRAIGHT
+    } else {
+        DXGI_ALPHA_MODE_IGNORE
+    }
+}
+
 #[derive(Debug)]
 pub struct Swapchain {
     pub(crate) inner: native::WeakPtr<dxgi1_4::IDXGISwapChain3>,
@@ -121,6 +212,17 @@ pub struct Swapchain {
     pub(crate) frame_queue: VecDeque<usize>,
     #[allow(dead_code)]
     pub(crate) rtv_heap: r::DescriptorHeap,
+    // The frame-latency waitable object of a `DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT`
+    // swapchain. Waiting on it blocks until a back-buffer is ready to be rendered to.
+    pub(crate) waitable: HANDLE,
+    // Window the swapchain was created for, and its client extent at creation
+    // time, used to detect a resize that makes the swapchain suboptimal.
+    pub(crate) wnd_handle: HWND,
+    pub(crate) extent: hal::window::Extent2D,
+    // Monotonically increasing value signalled to the acquire fence/semaphore.
+    // DX12 fences only ever move forward, so every acquire has to bump this or
+    // a caller waiting on the fence would unblock for the wrong frame.
+    pub(crate) sync_value: u64,
     // need to associate raw image pointers with the swapchain so they can be properly released
     // when the swapchain is destroyed
     pub(crate) resources: Vec<native::Resource>,
@@ -129,23 +231,59 @@ pub struct Swapchain {
 impl hal::Swapchain<Backend> for Swapchain {
     unsafe fn acquire_image(
         &mut self,
-        _timout_ns: u64,
-        _semaphore: Option<&r::Semaphore>,
-        _fence: Option<&r::Fence>,
+        timeout_ns: u64,
+        semaphore: Option<&r::Semaphore>,
+        fence: Option<&r::Fence>,
     ) -> Result<(hal::SwapImageIndex, Option<hal::window::Suboptimal>), hal::AcquireError> {
-        // TODO: sync
-
-        if false {
-            // TODO: we need to block this at some point? (running out of backbuffers)
-            //let num_images = self.images.len();
-            let num_images = 1;
-            let index = self.next_frame;
-            self.frame_queue.push_back(index);
-            self.next_frame = (self.next_frame + 1) % num_images;
+        // Block on the frame-latency waitable until the presentation engine has
+        // freed a back-buffer, bounding the wait by the requested timeout. The
+        // handle is only set when the swapchain was created as waitable
+        // (`DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT`); if it wasn't,
+        // don't wait on a null handle.
+        if !self.waitable.is_null() {
+            // Round up to whole milliseconds, saturating so the conventional
+            // `u64::MAX` "wait forever" sentinel caps to `INFINITE` (`u32::MAX`)
+            // rather than overflowing.
+            let timeout_ms = (timeout_ns.saturating_add(999_999) / 1_000_000)
+                .min(u64::from(u32::max_value())) as u32;
+            if WaitForSingleObjectEx(self.waitable, timeout_ms, FALSE) == WAIT_TIMEOUT {
+                return Err(hal::AcquireError::NotReady);
+            }
+        }
+
+        let num_images = self.resources.len();
+        let index = self.inner.GetCurrentBackBufferIndex() as usize;
+        self.frame_queue.push_back(index);
+        self.next_frame = (self.next_frame + 1) % num_images;
+
+        // The image is ready now, so signal the passed sync primitives with a
+        // fresh, advancing value to keep submission ordering consistent with
+        // the Vulkan backend. Signalling a fixed value would leave the fence
+        // stuck after the first frame.
+        self.sync_value += 1;
+        let signal = self.sync_value;
+        if let Some(fence) = fence {
+            fence.raw.signal(signal);
         }
+        if let Some(semaphore) = semaphore {
+            semaphore.raw.signal(signal);
+        }
+
+        // If the window was resized since the swapchain was built, the caller
+        // needs to recreate it.
+        let mut rect: RECT = mem::zeroed();
+        if GetClientRect(self.wnd_handle as *mut _, &mut rect) == 0 {
+            return Err(hal::AcquireError::OutOfDate);
+        }
+        let width = (rect.right - rect.left) as u32;
+        let height = (rect.bottom - rect.top) as u32;
+        let suboptimal = if width != self.extent.width || height != self.extent.height {
+            Some(hal::window::Suboptimal)
+        } else {
+            None
+        };
 
-        // TODO:
-        Ok((self.inner.GetCurrentBackBufferIndex(), None))
+        Ok((index as hal::SwapImageIndex, suboptimal))
     }
 }
 
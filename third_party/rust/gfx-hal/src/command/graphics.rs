@@ -1,14 +1,19 @@
 //! `CommandBuffer` methods for graphics operations.
 use std::borrow::Borrow;
-use std::ops::Range;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut, Range};
 
 use super::{
     ClearColorRaw,
     ClearDepthStencilRaw,
     ClearValueRaw,
     CommandBuffer,
+    CommandBufferFlags,
+    CommandBufferInheritanceInfo,
     DescriptorSetOffset,
     Level,
+    MultiShot,
+    OneShot,
     Primary,
     RawCommandBuffer,
     RenderPassInlineEncoder,
@@ -17,7 +22,8 @@ use super::{
 };
 use crate::queue::capability::{Graphics, GraphicsOrCompute, Supports};
 use crate::Backend;
-use crate::{buffer, image, pso, query};
+use crate::queue::QueueFamilyId;
+use crate::{buffer, image, memory, pass, pso, query, IndexCount, InstanceCount, VertexCount, VertexOffset};
 
 /// A universal clear color supporting integer formats
 /// as well as the standard floating-point.
@@ -172,6 +178,199 @@ pub struct ImageBlit {
     pub dst_bounds: Range<image::Offset>,
 }
 
+/// Shadow copy of the dynamic graphics state a `CommandBuffer` has most
+/// recently recorded, used to drop redundant `bind`/`set` calls before they
+/// cross into the backend.
+///
+/// Recording-heavy frontends re-issue `bind_graphics_pipeline`, `set_viewports`,
+/// `set_scissors`, `set_blend_constants` and descriptor-set binds with identical
+/// arguments on every draw; each of those crosses an FFI boundary. A
+/// `CommandBuffer<B, C, S, L>` may hold an `Option<State<B>>` (the `None` case
+/// being a raw pass-through for callers who don't want the overhead) and, before
+/// forwarding to `self.raw`, ask the cache whether the incoming value actually
+/// changed. The tracked fields mirror the per-pass `State` used by the GL-style
+/// backends (bound pipeline, viewport/scissor rects, blend constants, stencil
+/// masks and reference, depth bias, and the descriptor set bound to each slot).
+///
+/// The cache is invalidated by `begin_render_pass_*`, and a pipeline change
+/// clears the dynamic state since binding a pipeline resets its dynamic-state
+/// defaults. `finish` does not invalidate it.
+#[derive(Debug)]
+pub struct State<B: Backend> {
+    pipeline: Option<*const B::GraphicsPipeline>,
+    viewports: Option<(u32, Vec<pso::Viewport>)>,
+    scissors: Option<(u32, Vec<pso::Rect>)>,
+    blend_constants: Option<pso::ColorValue>,
+    depth_bias: Option<pso::DepthBias>,
+    stencil_reference: [Option<pso::StencilValue>; 2],
+    stencil_read_mask: [Option<pso::StencilValue>; 2],
+    stencil_write_mask: [Option<pso::StencilValue>; 2],
+    descriptor_sets: Vec<Option<*const B::DescriptorSet>>,
+}
+
+impl<B: Backend> Default for State<B> {
+    fn default() -> Self {
+        State {
+            pipeline: None,
+            viewports: None,
+            scissors: None,
+            blend_constants: None,
+            depth_bias: None,
+            stencil_reference: [None; 2],
+            stencil_read_mask: [None; 2],
+            stencil_write_mask: [None; 2],
+            descriptor_sets: Vec::new(),
+        }
+    }
+}
+
+fn stencil_slots(faces: pso::Face) -> impl Iterator<Item = usize> {
+    let front = faces.contains(pso::Face::FRONT);
+    let back = faces.contains(pso::Face::BACK);
+    (0 .. 2).filter(move |&i| (i == 0 && front) || (i == 1 && back))
+}
+
+impl<B: Backend> State<B> {
+    /// Forget every cached value. Called whenever the recorded state may have
+    /// been reset behind the cache's back; the `begin_render_pass_*` methods
+    /// call it.
+    pub fn invalidate(&mut self) {
+        *self = State::default();
+    }
+
+    /// Forget the dynamic state that a pipeline bind resets to its defaults,
+    /// leaving the (just updated) pipeline handle in place.
+    fn invalidate_dynamic(&mut self) {
+        self.viewports = None;
+        self.scissors = None;
+        self.blend_constants = None;
+        self.depth_bias = None;
+        self.stencil_reference = [None; 2];
+        self.stencil_read_mask = [None; 2];
+        self.stencil_write_mask = [None; 2];
+    }
+
+    /// Returns `true` if `pipeline` differs from the bound one, updating the
+    /// cache. Binding a new pipeline clears the tracked dynamic state.
+    ///
+    /// Equality is by handle address: the cache assumes a live handle is never
+    /// aliased by a different object at the same address within a recording, so
+    /// callers that free and reallocate pipelines mid-recording must
+    /// [`invalidate`](State::invalidate) (e.g. on descriptor/pipeline pool
+    /// reset) to avoid an ABA match dropping a real bind.
+    pub fn set_graphics_pipeline(&mut self, pipeline: &B::GraphicsPipeline) -> bool {
+        let ptr = pipeline as *const _;
+        if self.pipeline == Some(ptr) {
+            return false;
+        }
+        self.pipeline = Some(ptr);
+        self.invalidate_dynamic();
+        true
+    }
+
+    /// Returns `true` if the descriptor set bound to `slot` changed.
+    ///
+    /// Like [`set_graphics_pipeline`](State::set_graphics_pipeline), equality is
+    /// by handle address and carries the same ABA caveat; it also only tracks
+    /// the set identity, not any dynamic offsets bound alongside it (see
+    /// [`invalidate_descriptor_set`](State::invalidate_descriptor_set)).
+    pub fn set_descriptor_set(&mut self, slot: usize, set: &B::DescriptorSet) -> bool {
+        if self.descriptor_sets.len() <= slot {
+            self.descriptor_sets.resize(slot + 1, None);
+        }
+        let ptr = set as *const _;
+        if self.descriptor_sets[slot] == Some(ptr) {
+            return false;
+        }
+        self.descriptor_sets[slot] = Some(ptr);
+        true
+    }
+
+    /// Forget the descriptor set cached for `slot`, so the next bind to it is
+    /// always forwarded. Used when a bind carries dynamic offsets, which the
+    /// cache does not track and so must never short-circuit.
+    fn invalidate_descriptor_set(&mut self, slot: usize) {
+        if slot < self.descriptor_sets.len() {
+            self.descriptor_sets[slot] = None;
+        }
+    }
+
+    /// Returns `true` if the viewport set changed.
+    pub fn set_viewports(&mut self, first: u32, viewports: Vec<pso::Viewport>) -> bool {
+        if self.viewports.as_ref() == Some(&(first, viewports.clone())) {
+            return false;
+        }
+        self.viewports = Some((first, viewports));
+        true
+    }
+
+    /// Returns `true` if the scissor set changed.
+    pub fn set_scissors(&mut self, first: u32, scissors: Vec<pso::Rect>) -> bool {
+        if self.scissors.as_ref() == Some(&(first, scissors.clone())) {
+            return false;
+        }
+        self.scissors = Some((first, scissors));
+        true
+    }
+
+    /// Returns `true` if the blend constants changed.
+    pub fn set_blend_constants(&mut self, cv: pso::ColorValue) -> bool {
+        if self.blend_constants == Some(cv) {
+            return false;
+        }
+        self.blend_constants = Some(cv);
+        true
+    }
+
+    /// Returns `true` if the depth bias changed.
+    pub fn set_depth_bias(&mut self, bias: pso::DepthBias) -> bool {
+        if self.depth_bias == Some(bias) {
+            return false;
+        }
+        self.depth_bias = Some(bias);
+        true
+    }
+
+    /// Returns `true` if `value` differs from the cached reference for any of
+    /// the selected `faces`.
+    pub fn set_stencil_reference(&mut self, faces: pso::Face, value: pso::StencilValue) -> bool {
+        let mut changed = false;
+        for slot in stencil_slots(faces) {
+            if self.stencil_reference[slot] != Some(value) {
+                self.stencil_reference[slot] = Some(value);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Returns `true` if `value` differs from the cached read mask for any of
+    /// the selected `faces`.
+    pub fn set_stencil_read_mask(&mut self, faces: pso::Face, value: pso::StencilValue) -> bool {
+        let mut changed = false;
+        for slot in stencil_slots(faces) {
+            if self.stencil_read_mask[slot] != Some(value) {
+                self.stencil_read_mask[slot] = Some(value);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Returns `true` if `value` differs from the cached write mask for any of
+    /// the selected `faces`.
+    pub fn set_stencil_write_mask(&mut self, faces: pso::Face, value: pso::StencilValue) -> bool {
+        let mut changed = false;
+        for slot in stencil_slots(faces) {
+            if self.stencil_write_mask[slot] != Some(value) {
+                self.stencil_write_mask[slot] = Some(value);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
 impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S, L> {
     /// Identical to the `RawCommandBuffer` method of the same name.
     pub unsafe fn clear_image<T>(
@@ -194,6 +393,20 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
         )
     }
 
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    ///
+    /// Unlike `clear_image`, this is valid while a render pass is active and
+    /// clears selected regions of the currently-bound color/depth attachments.
+    pub unsafe fn clear_attachments<T, U>(&mut self, clears: T, rects: U)
+    where
+        T: IntoIterator,
+        T::Item: Borrow<AttachmentClear>,
+        U: IntoIterator,
+        U::Item: Borrow<pso::ClearRect>,
+    {
+        self.raw.clear_attachments(clears, rects)
+    }
+
     /// Identical to the `RawCommandBuffer` method of the same name.
     pub unsafe fn bind_index_buffer(&mut self, ibv: buffer::IndexBufferView<B>) {
         self.raw.bind_index_buffer(ibv)
@@ -209,11 +422,25 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
+    ///
+    /// When a shadow [`State`] cache is attached it drops the call if the same
+    /// pipeline is already bound; binding a new pipeline resets the tracked
+    /// dynamic state to its defaults.
     pub unsafe fn bind_graphics_pipeline(&mut self, pipeline: &B::GraphicsPipeline) {
-        self.raw.bind_graphics_pipeline(pipeline)
+        let changed = match self.state {
+            Some(ref mut state) => state.set_graphics_pipeline(pipeline),
+            None => true,
+        };
+        if changed {
+            self.raw.bind_graphics_pipeline(pipeline)
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
+    ///
+    /// With a shadow [`State`] cache attached, the call is dropped when no
+    /// dynamic offsets are passed and every set in the range is already bound
+    /// to the same slot.
     pub unsafe fn bind_graphics_descriptor_sets<I, J>(
         &mut self,
         layout: &B::PipelineLayout,
@@ -226,8 +453,35 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
         J: IntoIterator,
         J::Item: Borrow<DescriptorSetOffset>,
     {
-        self.raw
-            .bind_graphics_descriptor_sets(layout, first_set, sets, offsets)
+        let sets: Vec<I::Item> = sets.into_iter().collect();
+        let offsets: Vec<J::Item> = offsets.into_iter().collect();
+        let changed = match self.state {
+            Some(ref mut state) if offsets.is_empty() => {
+                let mut changed = false;
+                for (i, set) in sets.iter().enumerate() {
+                    if state.set_descriptor_set(first_set + i, set.borrow()) {
+                        changed = true;
+                    }
+                }
+                changed
+            }
+            // A bind carrying dynamic offsets (e.g. per-draw offsets into one
+            // dynamic uniform/storage buffer) must never be dropped: the same
+            // set can be rebound with different offsets. Forget the cached sets
+            // for the touched slots so a later offset-less rebind still goes
+            // through, then forward unconditionally.
+            Some(ref mut state) => {
+                for i in 0 .. sets.len() {
+                    state.invalidate_descriptor_set(first_set + i);
+                }
+                true
+            }
+            None => true,
+        };
+        if changed {
+            self.raw
+                .bind_graphics_descriptor_sets(layout, first_set, sets, offsets)
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
@@ -236,7 +490,15 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
         T: IntoIterator,
         T::Item: Borrow<pso::Viewport>,
     {
-        self.raw.set_viewports(first_viewport, viewports)
+        let viewports: Vec<pso::Viewport> =
+            viewports.into_iter().map(|v| v.borrow().clone()).collect();
+        let changed = match self.state {
+            Some(ref mut state) => state.set_viewports(first_viewport, viewports.clone()),
+            None => true,
+        };
+        if changed {
+            self.raw.set_viewports(first_viewport, viewports)
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
@@ -245,27 +507,59 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
         T: IntoIterator,
         T::Item: Borrow<pso::Rect>,
     {
-        self.raw.set_scissors(first_scissor, scissors)
+        let scissors: Vec<pso::Rect> =
+            scissors.into_iter().map(|s| s.borrow().clone()).collect();
+        let changed = match self.state {
+            Some(ref mut state) => state.set_scissors(first_scissor, scissors.clone()),
+            None => true,
+        };
+        if changed {
+            self.raw.set_scissors(first_scissor, scissors)
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
     pub unsafe fn set_stencil_reference(&mut self, faces: pso::Face, value: pso::StencilValue) {
-        self.raw.set_stencil_reference(faces, value);
+        let changed = match self.state {
+            Some(ref mut state) => state.set_stencil_reference(faces, value),
+            None => true,
+        };
+        if changed {
+            self.raw.set_stencil_reference(faces, value);
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
     pub unsafe fn set_stencil_read_mask(&mut self, faces: pso::Face, value: pso::StencilValue) {
-        self.raw.set_stencil_read_mask(faces, value);
+        let changed = match self.state {
+            Some(ref mut state) => state.set_stencil_read_mask(faces, value),
+            None => true,
+        };
+        if changed {
+            self.raw.set_stencil_read_mask(faces, value);
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
     pub unsafe fn set_stencil_write_mask(&mut self, faces: pso::Face, value: pso::StencilValue) {
-        self.raw.set_stencil_write_mask(faces, value);
+        let changed = match self.state {
+            Some(ref mut state) => state.set_stencil_write_mask(faces, value),
+            None => true,
+        };
+        if changed {
+            self.raw.set_stencil_write_mask(faces, value);
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
     pub unsafe fn set_blend_constants(&mut self, cv: pso::ColorValue) {
-        self.raw.set_blend_constants(cv)
+        let changed = match self.state {
+            Some(ref mut state) => state.set_blend_constants(cv),
+            None => true,
+        };
+        if changed {
+            self.raw.set_blend_constants(cv)
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
@@ -280,7 +574,13 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
 
     /// Identical to the `RawCommandBuffer` method of the same name.
     pub unsafe fn set_depth_bias(&mut self, depth_bias: pso::DepthBias) {
-        self.raw.set_depth_bias(depth_bias);
+        let changed = match self.state {
+            Some(ref mut state) => state.set_depth_bias(depth_bias),
+            None => true,
+        };
+        if changed {
+            self.raw.set_depth_bias(depth_bias);
+        }
     }
 
     /// Identical to the `RawCommandBuffer` method of the same name.
@@ -327,6 +627,110 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
         self.raw
             .blit_image(src, src_layout, dst, dst_layout, filter, regions)
     }
+
+    /// Issue a `pipeline_barrier` described in terms of high-level
+    /// [`AccessType`](memory::AccessType)s instead of hand-assembled stage,
+    /// access and layout masks, transitioning any number of `images` and
+    /// `buffers` at once.
+    ///
+    /// The `prev` accesses are folded into the source stage and access mask
+    /// (an empty slice means top-of-pipe with no access), `next` into the
+    /// destination masks, and the required image layout transition is derived
+    /// from `next`: all of its accesses must agree on a single layout (a
+    /// read-write `General` access acts as the fallback), otherwise the
+    /// transition is ambiguous and we panic. Buffers carry no layout and only
+    /// pick up the folded access masks.
+    pub unsafe fn pipeline_barrier_access<'a, Ii, Ib>(
+        &mut self,
+        prev: &[memory::AccessType],
+        next: &[memory::AccessType],
+        images: Ii,
+        buffers: Ib,
+        families: Option<Range<QueueFamilyId>>,
+    ) where
+        Ii: IntoIterator<Item = (&'a B::Image, image::SubresourceRange)>,
+        Ib: IntoIterator<Item = &'a B::Buffer>,
+    {
+        let (src_stage, src_access, old_layout) = fold_access(prev);
+        let (dst_stage, dst_access, new_layout) = fold_access(next);
+
+        // `Access` mirrors the Vulkan `VkAccessFlags`, which are shared between
+        // image and buffer barriers, so the folded masks carry straight over.
+        let src_buf = buffer::Access::from_bits_truncate(src_access.bits());
+        let dst_buf = buffer::Access::from_bits_truncate(dst_access.bits());
+
+        let image_barriers = images.into_iter().map(|(target, range)| memory::Barrier::Image {
+            states: (src_access, old_layout) .. (dst_access, new_layout),
+            target,
+            families: families.clone(),
+            range,
+        });
+        let buffer_barriers = buffers.into_iter().map(|target| memory::Barrier::Buffer {
+            states: src_buf .. dst_buf,
+            target,
+            families: families.clone(),
+            range: None .. None,
+        });
+
+        self.raw.pipeline_barrier(
+            src_stage .. dst_stage,
+            memory::Dependencies::empty(),
+            image_barriers.chain(buffer_barriers),
+        )
+    }
+}
+
+/// Fold a set of accesses into a single source/destination stage and access
+/// mask plus the image layout they imply.
+///
+/// An empty slice yields top-of-pipe with no access and the `General` layout.
+/// The layout is required to be unambiguous: every access that pins a concrete
+/// layout must agree, with `AccessType::General` (read-write) acting as the
+/// fallback when nothing else constrains it.
+fn fold_access(accesses: &[memory::AccessType]) -> (pso::PipelineStage, image::Access, image::Layout) {
+    if accesses.is_empty() {
+        return (
+            pso::PipelineStage::TOP_OF_PIPE,
+            image::Access::empty(),
+            image::Layout::General,
+        );
+    }
+
+    let mut stage = pso::PipelineStage::empty();
+    let mut access = image::Access::empty();
+    let mut layout = None;
+    let mut saw_general = false;
+    let mut saw_undefined = false;
+    for usage in accesses {
+        let (s, a, l) = usage.info();
+        stage |= s;
+        access |= a;
+        match l {
+            // `General` and `Undefined` don't pin a concrete layout: the former
+            // is the read-write fallback, the latter means "discard the prior
+            // contents" (e.g. a freshly created resource as the `prev`). Only
+            // remember them if nothing else constrains the layout.
+            image::Layout::General => saw_general = true,
+            image::Layout::Undefined => saw_undefined = true,
+            l => {
+                if let Some(prev) = layout {
+                    assert_eq!(prev, l, "ambiguous layout transition across access types");
+                }
+                layout = Some(l);
+            }
+        }
+    }
+    // A concrete layout wins; otherwise fall back to `General` (read-write), and
+    // preserve `Undefined` only when that is the single thing we saw so that the
+    // source of a freshly created resource transitions out of `Undefined`.
+    let layout = layout.unwrap_or_else(|| {
+        if saw_general || !saw_undefined {
+            image::Layout::General
+        } else {
+            image::Layout::Undefined
+        }
+    });
+    (stage, access, layout)
 }
 
 impl<B: Backend, C: Supports<Graphics>, S: Shot> CommandBuffer<B, C, S, Primary> {
@@ -342,6 +746,11 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot> CommandBuffer<B, C, S, Primary>
         T: IntoIterator,
         T::Item: Borrow<ClearValue>,
     {
+        // Beginning a render pass resets the dynamic pipeline state, so the
+        // shadow cache can no longer assume anything about what is bound.
+        if let Some(ref mut state) = self.state {
+            state.invalidate();
+        }
         RenderPassInlineEncoder::new(self, render_pass, frame_buffer, render_area, clear_values)
     }
 
@@ -357,6 +766,9 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot> CommandBuffer<B, C, S, Primary>
         T: IntoIterator,
         T::Item: Borrow<ClearValue>,
     {
+        if let Some(ref mut state) = self.state {
+            state.invalidate();
+        }
         RenderPassSecondaryEncoder::new(self, render_pass, frame_buffer, render_area, clear_values)
     }
 }
@@ -396,3 +808,157 @@ impl<B: Backend, C: Supports<GraphicsOrCompute>, S: Shot, L: Level> CommandBuffe
         self.raw.write_timestamp(stage, query)
     }
 }
+
+/// The drawing and binding commands that are valid while recording inside an
+/// active subpass, reached through `Deref` on a standalone
+/// [`SubpassCommandBuffer`].
+///
+/// All methods forward to the wrapped raw command buffer and mirror the
+/// `RawCommandBuffer` methods of the same name.
+#[derive(Debug)]
+pub struct RenderSubpassCommon<B: Backend> {
+    pub(crate) raw: B::CommandBuffer,
+}
+
+impl<B: Backend> RenderSubpassCommon<B> {
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn bind_graphics_pipeline(&mut self, pipeline: &B::GraphicsPipeline) {
+        self.raw.bind_graphics_pipeline(pipeline)
+    }
+
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn bind_graphics_descriptor_sets<I, J>(
+        &mut self,
+        layout: &B::PipelineLayout,
+        first_set: usize,
+        sets: I,
+        offsets: J,
+    ) where
+        I: IntoIterator,
+        I::Item: Borrow<B::DescriptorSet>,
+        J: IntoIterator,
+        J::Item: Borrow<DescriptorSetOffset>,
+    {
+        self.raw
+            .bind_graphics_descriptor_sets(layout, first_set, sets, offsets)
+    }
+
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn set_viewports<T>(&mut self, first_viewport: u32, viewports: T)
+    where
+        T: IntoIterator,
+        T::Item: Borrow<pso::Viewport>,
+    {
+        self.raw.set_viewports(first_viewport, viewports)
+    }
+
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn set_scissors<T>(&mut self, first_scissor: u32, scissors: T)
+    where
+        T: IntoIterator,
+        T::Item: Borrow<pso::Rect>,
+    {
+        self.raw.set_scissors(first_scissor, scissors)
+    }
+
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn draw(&mut self, vertices: Range<VertexCount>, instances: Range<InstanceCount>) {
+        self.raw.draw(vertices, instances)
+    }
+
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn draw_indexed(
+        &mut self,
+        indices: Range<IndexCount>,
+        base_vertex: VertexOffset,
+        instances: Range<InstanceCount>,
+    ) {
+        self.raw.draw_indexed(indices, base_vertex, instances)
+    }
+
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn clear_attachments<T, U>(&mut self, clears: T, rects: U)
+    where
+        T: IntoIterator,
+        T::Item: Borrow<AttachmentClear>,
+        U: IntoIterator,
+        U::Item: Borrow<pso::ClearRect>,
+    {
+        self.raw.clear_attachments(clears, rects)
+    }
+}
+
+/// A secondary command buffer whose recording is confined to a single subpass.
+///
+/// Unlike [`RenderPassSecondaryEncoder`], it owns its backing buffer and is not
+/// tied to the lifetime of a primary buffer, so it can be recorded on a worker
+/// thread and later executed by a primary buffer while the matching render pass
+/// is active. The inheritance info describing the enclosing subpass is captured
+/// once by [`begin`](SubpassCommandBuffer::begin), and all drawing happens
+/// through the [`RenderSubpassCommon`] target reached by `Deref`.
+#[derive(Debug)]
+pub struct SubpassCommandBuffer<B: Backend, S: Shot> {
+    inner: RenderSubpassCommon<B>,
+    _marker: PhantomData<S>,
+}
+
+impl<B: Backend, S: Shot> SubpassCommandBuffer<B, S> {
+    /// Wrap a raw command buffer without beginning recording.
+    pub fn new(raw: B::CommandBuffer) -> Self {
+        SubpassCommandBuffer {
+            inner: RenderSubpassCommon { raw },
+            _marker: PhantomData,
+        }
+    }
+
+    /// Finish recording the subpass command buffer.
+    pub unsafe fn finish(&mut self) {
+        self.inner.raw.finish()
+    }
+}
+
+impl<B: Backend> SubpassCommandBuffer<B, OneShot> {
+    /// Begin recording a one-shot subpass command buffer, capturing the
+    /// inheritance info of the `subpass` (and optional `framebuffer`) it will
+    /// be spliced into.
+    pub unsafe fn begin(&mut self, subpass: pass::Subpass<B>, framebuffer: Option<&B::Framebuffer>) {
+        let inheritance = CommandBufferInheritanceInfo {
+            subpass: Some(subpass),
+            framebuffer,
+            ..CommandBufferInheritanceInfo::default()
+        };
+        self.inner.raw.begin(
+            CommandBufferFlags::ONE_TIME_SUBMIT | CommandBufferFlags::RENDER_PASS_CONTINUE,
+            inheritance,
+        )
+    }
+}
+
+impl<B: Backend> SubpassCommandBuffer<B, MultiShot> {
+    /// Begin recording a multi-shot subpass command buffer, capturing the
+    /// inheritance info of the `subpass` (and optional `framebuffer`) it will
+    /// be spliced into.
+    pub unsafe fn begin(&mut self, subpass: pass::Subpass<B>, framebuffer: Option<&B::Framebuffer>) {
+        let inheritance = CommandBufferInheritanceInfo {
+            subpass: Some(subpass),
+            framebuffer,
+            ..CommandBufferInheritanceInfo::default()
+        };
+        self.inner
+            .raw
+            .begin(CommandBufferFlags::RENDER_PASS_CONTINUE, inheritance)
+    }
+}
+
+impl<B: Backend, S: Shot> Deref for SubpassCommandBuffer<B, S> {
+    type Target = RenderSubpassCommon<B>;
+    fn deref(&self) -> &RenderSubpassCommon<B> {
+        &self.inner
+    }
+}
+
+impl<B: Backend, S: Shot> DerefMut for SubpassCommandBuffer<B, S> {
+    fn deref_mut(&mut self) -> &mut RenderSubpassCommon<B> {
+        &mut self.inner
+    }
+}
@@ -0,0 +1,241 @@
+//! Types to describe the properties of memory, and a high-level vocabulary
+//! for expressing synchronization in terms of concrete resource usages.
+
+use crate::buffer;
+use crate::image::{self, Access, Layout};
+use crate::pso::PipelineStage;
+use crate::queue::QueueFamilyId;
+use crate::Backend;
+
+use std::ops::Range;
+
+bitflags!(
+    /// Memory property flags.
+    pub struct Properties: u16 {
+        /// Device local memory on the GPU.
+        const DEVICE_LOCAL   = 0x1;
+
+        /// Host visible memory can be accessed by the CPU.
+        ///
+        /// Backends must provide at least one cpu visible memory.
+        const CPU_VISIBLE   = 0x2;
+
+        /// CPU-GPU coherent.
+        ///
+        /// Non-coherent memory requires explicit flushing.
+        const COHERENT     = 0x4;
+
+        /// Cached memory by the CPU
+        const CPU_CACHED = 0x8;
+
+        /// Memory that may be lazily allocated as needed on first use.
+        ///
+        /// It *must not* be used with `CPU_VISIBLE`.
+        const LAZILY_ALLOCATED = 0x10;
+    }
+);
+
+bitflags!(
+    /// Barrier dependency flags.
+    pub struct Dependencies: u32 {
+        /// Specifies that the synchronization scope is confined to the region
+        /// of each resource touched by the framebuffer.
+        const BY_REGION = 0x1;
+        // const VIEW_LOCAL = 0x2;
+        /// Specifies that the dependency is device-group-local.
+        const DEVICE_GROUP = 0x4;
+    }
+);
+
+/// A memory barrier for synchronizing access to a resource, and optionally
+/// transitioning its image layout or transferring queue family ownership.
+#[allow(missing_docs)]
+pub enum Barrier<'a, B: Backend> {
+    /// Applies the access mask transition to all buffers.
+    AllBuffers(Range<buffer::Access>),
+    /// Applies the access mask transition to all images.
+    AllImages(Range<image::Access>),
+    /// A barrier for a single buffer.
+    Buffer {
+        /// The access flags before and after the barrier.
+        states: Range<buffer::State>,
+        /// The buffer the barrier controls.
+        target: &'a B::Buffer,
+        /// A queue family ownership transfer, if the barrier performs one.
+        families: Option<Range<QueueFamilyId>>,
+        /// The offset range within the buffer the barrier applies to, or the
+        /// whole buffer when both bounds are `None`.
+        range: Range<Option<buffer::Offset>>,
+    },
+    /// A barrier for a single image.
+    Image {
+        /// The access flags and layout before and after the barrier.
+        states: Range<image::State>,
+        /// The image the barrier controls.
+        target: &'a B::Image,
+        /// A queue family ownership transfer, if the barrier performs one.
+        families: Option<Range<QueueFamilyId>>,
+        /// The subresource range the barrier applies to.
+        range: image::SubresourceRange,
+    },
+}
+
+/// A concrete way in which a resource is used by the pipeline.
+///
+/// Hand-assembling the `(PipelineStage, Access, image::Layout)` triples that a
+/// `pipeline_barrier` needs is error-prone. An `AccessType` names a single
+/// usage and statically maps to the triple it implies, so a barrier can be
+/// expressed declaratively as "these accesses were happening, now these ones
+/// will" via [`CommandBuffer::pipeline_barrier_access`].
+///
+/// [`CommandBuffer::pipeline_barrier_access`]: ../command/struct.CommandBuffer.html#method.pipeline_barrier_access
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AccessType {
+    /// No access. Useful as the `prev` of a freshly created resource.
+    Nothing,
+    /// Read as an indirect draw/dispatch command buffer.
+    IndirectBuffer,
+    /// Read as an index buffer.
+    IndexBuffer,
+    /// Read as a vertex buffer.
+    VertexBuffer,
+    /// Read as a uniform buffer in the vertex shader.
+    VertexShaderReadUniformBuffer,
+    /// Read as a sampled image in the vertex shader.
+    VertexShaderReadSampledImage,
+    /// Read as a sampled image in the fragment shader.
+    FragmentShaderReadSampledImage,
+    /// Read as a uniform buffer in the fragment shader.
+    FragmentShaderReadUniformBuffer,
+    /// Read as a color attachment (e.g. blending).
+    ColorAttachmentRead,
+    /// Written as a color attachment.
+    ColorAttachmentWrite,
+    /// Written as a depth/stencil attachment.
+    DepthStencilAttachmentWrite,
+    /// Read as a sampled image in a compute shader.
+    ComputeShaderReadSampledImage,
+    /// Read as a uniform buffer in a compute shader.
+    ComputeShaderReadUniformBuffer,
+    /// Written via image or storage-buffer store in a compute shader.
+    ComputeShaderWrite,
+    /// Read as the source of a transfer operation.
+    TransferRead,
+    /// Written as the destination of a transfer operation.
+    TransferWrite,
+    /// Read by the host.
+    HostRead,
+    /// Written by the host.
+    HostWrite,
+    /// Used for presentation on a swapchain.
+    Present,
+    /// Arbitrary read-write access, using the `General` layout. A coarse
+    /// fallback for usages that don't map to any single variant.
+    General,
+}
+
+impl AccessType {
+    /// The pipeline stage, access flags and image layout implied by this usage.
+    ///
+    /// Buffer usages report the layout they would have if applied to an image,
+    /// which callers targeting buffers simply ignore.
+    pub fn info(self) -> (PipelineStage, Access, Layout) {
+        match self {
+            AccessType::Nothing => {
+                (PipelineStage::TOP_OF_PIPE, Access::empty(), Layout::Undefined)
+            }
+            AccessType::IndirectBuffer => (
+                PipelineStage::DRAW_INDIRECT,
+                Access::MEMORY_READ,
+                Layout::General,
+            ),
+            AccessType::IndexBuffer => (
+                PipelineStage::VERTEX_INPUT,
+                Access::MEMORY_READ,
+                Layout::General,
+            ),
+            AccessType::VertexBuffer => (
+                PipelineStage::VERTEX_INPUT,
+                Access::MEMORY_READ,
+                Layout::General,
+            ),
+            AccessType::VertexShaderReadUniformBuffer => (
+                PipelineStage::VERTEX_SHADER,
+                Access::SHADER_READ,
+                Layout::General,
+            ),
+            AccessType::VertexShaderReadSampledImage => (
+                PipelineStage::VERTEX_SHADER,
+                Access::SHADER_READ,
+                Layout::ShaderReadOnlyOptimal,
+            ),
+            AccessType::FragmentShaderReadSampledImage => (
+                PipelineStage::FRAGMENT_SHADER,
+                Access::SHADER_READ,
+                Layout::ShaderReadOnlyOptimal,
+            ),
+            AccessType::FragmentShaderReadUniformBuffer => (
+                PipelineStage::FRAGMENT_SHADER,
+                Access::SHADER_READ,
+                Layout::General,
+            ),
+            AccessType::ColorAttachmentRead => (
+                PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                Access::COLOR_ATTACHMENT_READ,
+                Layout::ColorAttachmentOptimal,
+            ),
+            AccessType::ColorAttachmentWrite => (
+                PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                Access::COLOR_ATTACHMENT_WRITE,
+                Layout::ColorAttachmentOptimal,
+            ),
+            AccessType::DepthStencilAttachmentWrite => (
+                PipelineStage::EARLY_FRAGMENT_TESTS | PipelineStage::LATE_FRAGMENT_TESTS,
+                Access::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                Layout::DepthStencilAttachmentOptimal,
+            ),
+            AccessType::ComputeShaderReadSampledImage => (
+                PipelineStage::COMPUTE_SHADER,
+                Access::SHADER_READ,
+                Layout::ShaderReadOnlyOptimal,
+            ),
+            AccessType::ComputeShaderReadUniformBuffer => (
+                PipelineStage::COMPUTE_SHADER,
+                Access::SHADER_READ,
+                Layout::General,
+            ),
+            AccessType::ComputeShaderWrite => (
+                PipelineStage::COMPUTE_SHADER,
+                Access::SHADER_WRITE,
+                Layout::General,
+            ),
+            AccessType::TransferRead => (
+                PipelineStage::TRANSFER,
+                Access::TRANSFER_READ,
+                Layout::TransferSrcOptimal,
+            ),
+            AccessType::TransferWrite => (
+                PipelineStage::TRANSFER,
+                Access::TRANSFER_WRITE,
+                Layout::TransferDstOptimal,
+            ),
+            AccessType::HostRead => {
+                (PipelineStage::HOST, Access::HOST_READ, Layout::General)
+            }
+            AccessType::HostWrite => {
+                (PipelineStage::HOST, Access::HOST_WRITE, Layout::General)
+            }
+            AccessType::Present => (
+                PipelineStage::BOTTOM_OF_PIPE,
+                Access::MEMORY_READ,
+                Layout::Present,
+            ),
+            AccessType::General => (
+                PipelineStage::ALL_COMMANDS,
+                Access::MEMORY_READ | Access::MEMORY_WRITE,
+                Layout::General,
+            ),
+        }
+    }
+}